@@ -0,0 +1,321 @@
+//! Configuration understood by the Blockless multi-tenant embedder: which
+//! modules make up a run, what they're allowed to touch, and where their
+//! stdio goes.
+
+use crate::pipe::OutputFile;
+use crate::{Error, WasiFile};
+
+/// Where a guest's stdout should be sent.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Stdout {
+    /// Discard everything written.
+    Null,
+    /// Inherit the embedder's own stdout.
+    Inherit,
+    /// Write to the file at this path, creating it if it doesn't exist.
+    FileName(String),
+}
+
+impl Stdout {
+    /// Build the `WasiFile` this destination wires up to. `Inherit` and
+    /// `FileName` are backed by a real OS handle and `Null` by
+    /// `std::io::sink()`; all of them get `OutputFile`'s blocking fast
+    /// path rather than `WritePipe`'s poll-to-ready machinery.
+    pub fn into_wasi_file(self) -> Result<Box<dyn WasiFile>, Error> {
+        Ok(match self {
+            Stdout::Null => Box::new(OutputFile::new(std::io::sink())),
+            Stdout::Inherit => Box::new(OutputFile::new(std::io::stdout())),
+            Stdout::FileName(path) => Box::new(OutputFile::new(
+                std::fs::OpenOptions::new()
+                    .create(true)
+                    .append(true)
+                    .open(path)?,
+            )),
+        })
+    }
+}
+
+/// Where a guest's stderr should be sent. See [`Stdout`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Stderr {
+    Null,
+    Inherit,
+    FileName(String),
+}
+
+impl Stderr {
+    /// See [`Stdout::into_wasi_file`].
+    pub fn into_wasi_file(self) -> Result<Box<dyn WasiFile>, Error> {
+        Ok(match self {
+            Stderr::Null => Box::new(OutputFile::new(std::io::sink())),
+            Stderr::Inherit => Box::new(OutputFile::new(std::io::stderr())),
+            Stderr::FileName(path) => Box::new(OutputFile::new(
+                std::fs::OpenOptions::new()
+                    .create(true)
+                    .append(true)
+                    .open(path)?,
+            )),
+        })
+    }
+}
+
+/// Whether a [`BlocklessModule`] is the run's entry point or a library
+/// linked in to support it.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum ModuleType {
+    Entry,
+    Module,
+}
+
+/// A single `.wasm` file making up a multi-module run, and how it's used.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BlocklessModule {
+    pub module_type: ModuleType,
+    pub name: String,
+    pub file: String,
+    pub md5: Option<String>,
+}
+
+/// An allowlisted resource a guest may access - an absolute file path
+/// prefix, or a network host (optionally `host:port`) - gating the
+/// capability-scoped `WasiDir`/`WasiFile` implementations the embedder
+/// hands out.
+///
+/// `url` is always an absolute path for `"file"` permissions: callers
+/// checking a WASI path (which is relative to whatever directory it was
+/// opened under) must resolve it to an absolute path first - see
+/// [`crate::permissions::PermissionedDir`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Permission {
+    pub schema: String,
+    pub url: String,
+}
+
+impl Permission {
+    /// Does this entry cover `target` under the given `schema` (e.g.
+    /// `"file"` for absolute paths, `"tcp"` for `host:port` addresses)? The
+    /// schema must match exactly, and `url` must cover `target` by whole
+    /// path segment (for `"file"`) or by whole host (for `"tcp"`) - never
+    /// by raw byte prefix, so a permission for `/var/data` does not also
+    /// cover `/var/data-secret`, and a permission for `93.184.21` does not
+    /// also cover `93.184.210.0`.
+    pub fn allows(&self, schema: &str, target: &str) -> bool {
+        if self.schema != schema {
+            return false;
+        }
+        match schema {
+            "tcp" => Self::allows_host_port(&self.url, target),
+            _ => Self::allows_path(&self.url, target),
+        }
+    }
+
+    /// Whole-segment path prefix match: every `/`-separated segment of
+    /// `allowed` must equal the segment at the same position in `target`.
+    fn allows_path(allowed: &str, target: &str) -> bool {
+        let mut target_segments = target.split('/').filter(|s| !s.is_empty());
+        for allowed_segment in allowed.split('/').filter(|s| !s.is_empty()) {
+            match target_segments.next() {
+                Some(target_segment) if target_segment == allowed_segment => continue,
+                _ => return false,
+            }
+        }
+        true
+    }
+
+    /// Host match, with the port compared separately so a host-only
+    /// permission (no `:port`) allows any port, but never matches on a
+    /// shared numeric prefix of the host itself.
+    fn allows_host_port(allowed: &str, target: &str) -> bool {
+        let (allowed_host, allowed_port) = Self::split_host_port(allowed);
+        let (target_host, target_port) = Self::split_host_port(target);
+        if allowed_host != target_host {
+            return false;
+        }
+        match allowed_port {
+            Some(_) => allowed_port == target_port,
+            None => true,
+        }
+    }
+
+    /// Split `host:port` into its parts, treating a trailing `:`-prefixed
+    /// run of digits as the port. A bracketed IPv6 literal (`[::1]:8080`,
+    /// the form `SocketAddr::to_string` produces) still splits correctly
+    /// since the bracket close comes before the final colon.
+    fn split_host_port(s: &str) -> (&str, Option<&str>) {
+        match s.rsplit_once(':') {
+            Some((host, port)) if !port.is_empty() && port.bytes().all(|b| b.is_ascii_digit()) => {
+                (host, Some(port))
+            }
+            _ => (s, None),
+        }
+    }
+}
+
+/// Configuration for a driver - an out-of-process extension a guest can
+/// call into, e.g. for object storage - available to a run.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DriverConfig {
+    pub schema: String,
+    pub path: String,
+}
+
+/// Verbosity of the embedder's own logging for a run, independent of
+/// whatever the guest itself writes to stdout/stderr.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub enum LoggerLevel {
+    Error,
+    Warn,
+    Info,
+    Debug,
+    Trace,
+}
+
+/// The schema version of a serialized [`BlocklessConfig`], so the
+/// embedder can evolve the config format without breaking guests pinned
+/// to an older one.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum BlocklessConfigVersion {
+    Version0,
+}
+
+/// Everything needed to run one Blockless guest: its modules, what it's
+/// allowed to touch, and where its stdio goes.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BlocklessConfig {
+    pub version: BlocklessConfigVersion,
+    pub modules: Vec<BlocklessModule>,
+    pub permissions: Vec<Permission>,
+    pub drivers: Vec<DriverConfig>,
+    pub stdout: Stdout,
+    pub stderr: Stderr,
+}
+
+/// Link and run the multi-module application described by `config` on a
+/// freshly built [`WasiCtx`]: `config.permissions` scopes every preopen
+/// `preopen` adds (see [`WasiCtxBuilder::permissions`]), `config.stdout`/
+/// `config.stderr` become the guest's stdio via [`Stdout::into_wasi_file`]/
+/// [`Stderr::into_wasi_file`], and [`ModuleType::Module`] entries are
+/// instantiated in dependency order -
+/// determined from each module's own wasm imports, not manifest order - so
+/// a module that imports another non-entry module is linked after it.
+/// Every instantiated module's exports are registered in `linker` under its
+/// own name so that importers resolve them the way a regular wasm module
+/// imports functions from another by module name.
+///
+/// `random`/`clocks`/`sched` are the same resources any [`WasiCtxBuilder::new`]
+/// needs; `preopen` lets the embedder add whatever preopened dirs/sockets
+/// the manifest's modules expect - it runs after `config.permissions` is
+/// applied, so anything it preopens is gated by the manifest's allowlist.
+/// `add_to_linker` is whatever `wasi_common::define_wasi!` produced for this
+/// crate's own [`WasiCtx`] - wiring up WASI hostcalls is the embedder's job
+/// everywhere else in this crate, and multi-module linking is no exception.
+/// The returned code is the entry module's exit status: a `proc_exit`
+/// unwinds as an [`crate::I32Exit`] trap that's unwrapped back into a code
+/// here via [`crate::i32_exit_status`], and a `_start` that returns
+/// normally exits 0.
+///
+/// `config.drivers` describes out-of-process extensions a guest can call
+/// into; there's no `WasiFile`/`WasiDir` hook for those yet, so `run` does
+/// not wire them up.
+#[cfg_attr(docsrs, doc(cfg(feature = "wasmtime")))]
+#[cfg(feature = "wasmtime")]
+pub fn run(
+    config: &BlocklessConfig,
+    engine: &wasmtime::Engine,
+    random: Box<dyn crate::RngCore + Send + Sync>,
+    clocks: crate::WasiClocks,
+    sched: Box<dyn crate::WasiSched>,
+    preopen: impl FnOnce(crate::WasiCtxBuilder) -> Result<crate::WasiCtxBuilder, Error>,
+    add_to_linker: impl Fn(&mut wasmtime::Linker<crate::WasiCtx>) -> anyhow::Result<()>,
+) -> Result<i32, Error> {
+    let builder =
+        crate::WasiCtxBuilder::new(random, clocks, sched).permissions(config.permissions.clone());
+    let ctx = preopen(builder)?
+        .stdout(config.stdout.clone().into_wasi_file()?)
+        .stderr(config.stderr.clone().into_wasi_file()?)
+        .build();
+    let mut store = wasmtime::Store::new(engine, ctx);
+
+    let mut linker = wasmtime::Linker::new(engine);
+    add_to_linker(&mut linker).map_err(Error::trap)?;
+
+    let mut libraries = Vec::new();
+    let mut entry = None;
+    for module in &config.modules {
+        let wasm =
+            wasmtime::Module::from_file(engine, &module.file).map_err(|e| Error::trap(e.into()))?;
+        match module.module_type {
+            ModuleType::Module => libraries.push((module, wasm)),
+            ModuleType::Entry => entry = Some(wasm),
+        }
+    }
+    let entry = entry.ok_or_else(|| Error::trap(anyhow::anyhow!("config has no entry module")))?;
+
+    for i in dependency_order(&libraries)? {
+        let (module, wasm) = &libraries[i];
+        let instance = linker
+            .instantiate(&mut store, wasm)
+            .map_err(|e| Error::trap(e.into()))?;
+        linker
+            .instance(&mut store, &module.name, instance)
+            .map_err(|e| Error::trap(e.into()))?;
+    }
+
+    let instance = linker
+        .instantiate(&mut store, &entry)
+        .map_err(|e| Error::trap(e.into()))?;
+    let start = instance
+        .get_typed_func::<(), ()>(&mut store, "_start")
+        .map_err(|e| Error::trap(e.into()))?;
+
+    match start.call(&mut store, ()) {
+        Ok(()) => Ok(0),
+        Err(trap) => crate::i32_exit_status(trap.into()).map_err(Error::trap),
+    }
+}
+
+/// Topologically sort `libraries` so a module imports-from another module
+/// only after that other module appears earlier in the result, using Kahn's
+/// algorithm over the dependency edges discovered from each module's own
+/// wasm imports (an import whose module name matches another library's
+/// `name` is a dependency on it; anything else - WASI hostcalls, an
+/// unresolved name - isn't a library dependency and is ignored here).
+#[cfg(feature = "wasmtime")]
+fn dependency_order(
+    libraries: &[(&BlocklessModule, wasmtime::Module)],
+) -> Result<Vec<usize>, Error> {
+    let index_by_name: std::collections::HashMap<&str, usize> = libraries
+        .iter()
+        .enumerate()
+        .map(|(i, (module, _))| (module.name.as_str(), i))
+        .collect();
+    let depends_on: Vec<std::collections::HashSet<usize>> = libraries
+        .iter()
+        .enumerate()
+        .map(|(i, (_, wasm))| {
+            wasm.imports()
+                .filter_map(|import| index_by_name.get(import.module()).copied())
+                .filter(|&dep| dep != i)
+                .collect()
+        })
+        .collect();
+
+    let mut linked = vec![false; libraries.len()];
+    let mut order = Vec::with_capacity(libraries.len());
+    while order.len() < libraries.len() {
+        let next = (0..libraries.len())
+            .find(|&i| !linked[i] && depends_on[i].iter().all(|&dep| linked[dep]));
+        match next {
+            Some(i) => {
+                linked[i] = true;
+                order.push(i);
+            }
+            None => {
+                return Err(Error::trap(anyhow::anyhow!(
+                    "cyclic dependency among config modules"
+                )))
+            }
+        }
+    }
+    Ok(order)
+}
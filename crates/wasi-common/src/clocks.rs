@@ -0,0 +1,42 @@
+use cap_std::time::{Duration, Instant, SystemTime};
+use std::any::Any;
+
+/// A WASI clock representing wall-clock time, as seen by the guest.
+pub trait WasiSystemClock: Send + Sync {
+    fn as_any(&self) -> &dyn Any;
+
+    /// Return the current value of the clock.
+    fn now(&self, precision: Duration) -> SystemTime;
+
+    /// The resolution of the clock.
+    fn resolution(&self) -> Duration;
+}
+
+/// A WASI clock representing monotonic time, as seen by the guest.
+pub trait WasiMonotonicClock: Send + Sync {
+    fn as_any(&self) -> &dyn Any;
+
+    /// Return the current value of the clock.
+    fn now(&self, precision: Duration) -> Instant;
+
+    /// The resolution of the clock.
+    fn resolution(&self) -> Duration;
+}
+
+/// The set of clocks a `WasiCtx` needs access to.
+pub struct WasiClocks {
+    pub system: Box<dyn WasiSystemClock>,
+    pub monotonic: Box<dyn WasiMonotonicClock>,
+    pub creation_time: Instant,
+}
+
+/// A timestamp argument accepted by `fd_filestat_set_times` (and, in the
+/// future, any other hostcall that needs to express "now", "this exact
+/// value", or "leave it unchanged" for a single timestamp field).
+#[derive(Debug, Copy, Clone)]
+pub enum SystemTimeSpec {
+    /// Set the timestamp to the given absolute value.
+    Absolute(SystemTime),
+    /// Set the timestamp to the current value of the system clock.
+    SymbolicNow,
+}
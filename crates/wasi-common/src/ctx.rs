@@ -0,0 +1,229 @@
+use crate::clocks::WasiClocks;
+use crate::dir::WasiDir;
+use crate::file::WasiFile;
+use crate::permissions::{PermissionedDir, PermissionedFile};
+use crate::sched::WasiSched;
+use crate::string_array::StringArray;
+use crate::table::Table;
+use crate::{Error, ErrorExt, Permission};
+use cap_rand::RngCore;
+use std::sync::{Arc, RwLock};
+
+/// What a guest's `proc_exit` call should do once it reaches the embedder.
+/// Only the `proc_exit` hostcalls consult this setting.
+///
+/// The two variants exist because "exiting" means very different things
+/// depending on who's hosting the guest:
+///
+/// * A CLI running a single module wants `proc_exit` to end the process,
+///   matching native WASI behavior people expect from the command line.
+/// * A multi-tenant embedder - e.g. Blockless, running many guests in one
+///   process - cannot let one guest call `std::process::exit` and take
+///   every other tenant down with it. It needs the exit code unwound back
+///   to the host `call` so it can tear down just that one instance.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default)]
+pub enum ExitBehavior {
+    /// Raise the exit code as a trappable `I32Exit` error that unwinds the
+    /// wasm call stack back to the host, without ever touching
+    /// `std::process::exit`. This is the right default for embedders.
+    #[default]
+    TrapAndUnwind,
+    /// Call `std::process::exit` directly, terminating the whole process.
+    /// This is what the Wasmtime CLI wants, and is opt-in for everyone
+    /// else via [`WasiCtx::set_exit_behavior`].
+    Terminate,
+}
+
+/// The state we associate with a single WASI instance, shared by both the
+/// `preview_0` and `preview_1` snapshots.
+pub struct WasiCtx {
+    pub args: StringArray,
+    pub env: StringArray,
+    pub random: Box<dyn RngCore + Send + Sync>,
+    pub clocks: WasiClocks,
+    pub sched: Box<dyn WasiSched>,
+    pub table: Arc<RwLock<Table>>,
+    exit_behavior: ExitBehavior,
+}
+
+impl WasiCtx {
+    pub fn new(
+        random: Box<dyn RngCore + Send + Sync>,
+        clocks: WasiClocks,
+        sched: Box<dyn WasiSched>,
+        table: Table,
+    ) -> Self {
+        let s = WasiCtx {
+            args: StringArray::new(),
+            env: StringArray::new(),
+            random,
+            clocks,
+            sched,
+            table: Arc::new(RwLock::new(table)),
+            exit_behavior: ExitBehavior::default(),
+        };
+        s.set_stdin(Box::new(crate::pipe::ReadPipe::new(std::io::empty())));
+        s.set_stdout(Box::new(crate::pipe::WritePipe::new(std::io::sink())));
+        s.set_stderr(Box::new(crate::pipe::WritePipe::new(std::io::sink())));
+        s
+    }
+
+    pub fn insert_file(&self, fd: u32, file: Box<dyn WasiFile>) {
+        self.table().insert_at(fd, Arc::new(file) as _);
+    }
+
+    pub fn insert_dir(&self, fd: u32, dir: Box<dyn WasiDir>) {
+        self.table().insert_at(fd, Arc::new(dir) as _);
+    }
+
+    pub fn set_stdin(&self, file: Box<dyn WasiFile>) {
+        self.insert_file(0, file);
+    }
+
+    pub fn set_stdout(&self, file: Box<dyn WasiFile>) {
+        self.insert_file(1, file);
+    }
+
+    pub fn set_stderr(&self, file: Box<dyn WasiFile>) {
+        self.insert_file(2, file);
+    }
+
+    pub fn push_preopened_dir(
+        &self,
+        dir: Box<dyn WasiDir>,
+        path: impl AsRef<std::path::Path>,
+    ) -> Result<(), Error> {
+        let _ = path;
+        let mut table = self.table();
+        let fd = table.push(Arc::new(dir)).map_err(|_| Error::too_big())?;
+        let _ = fd;
+        Ok(())
+    }
+
+    pub fn table(&self) -> std::sync::RwLockWriteGuard<'_, Table> {
+        self.table.write().unwrap()
+    }
+
+    /// Look up a file-like resource (a regular file, pipe, or - since it's
+    /// just another `WasiFile` impl - a socket) by fd, for the `fd_read`/
+    /// `fd_write`/`poll_oneoff`/`sock_accept` hostcalls to dispatch to.
+    pub fn get_file(&self, fd: u32) -> Result<Arc<Box<dyn WasiFile>>, Error> {
+        self.table.read().unwrap().get(fd).ok_or_else(Error::badf)
+    }
+
+    /// Look up a directory resource by fd.
+    pub fn get_dir(&self, fd: u32) -> Result<Arc<Box<dyn WasiDir>>, Error> {
+        self.table.read().unwrap().get(fd).ok_or_else(Error::badf)
+    }
+
+    /// Choose what a guest's `proc_exit` should do once it reaches this
+    /// embedder. Defaults to
+    /// [`ExitBehavior::TrapAndUnwind`]; the Wasmtime CLI opts into
+    /// [`ExitBehavior::Terminate`] via [`Self::set_exit_behavior`] so that
+    /// `wasmtime run` keeps its familiar "process exits with the guest's
+    /// code" behavior.
+    pub fn set_exit_behavior(&mut self, behavior: ExitBehavior) {
+        self.exit_behavior = behavior;
+    }
+
+    pub fn exit_behavior(&self) -> ExitBehavior {
+        self.exit_behavior
+    }
+}
+
+/// Incrementally builds a [`WasiCtx`]: wire up stdio, preopened
+/// directories, and - for embedders handing a guest a listening socket -
+/// preopened sockets, before handing back the finished context via
+/// [`Self::build`].
+pub struct WasiCtxBuilder {
+    ctx: WasiCtx,
+    permissions: Arc<Vec<Permission>>,
+}
+
+impl WasiCtxBuilder {
+    pub fn new(
+        random: Box<dyn RngCore + Send + Sync>,
+        clocks: WasiClocks,
+        sched: Box<dyn WasiSched>,
+    ) -> Self {
+        WasiCtxBuilder {
+            ctx: WasiCtx::new(random, clocks, sched, Table::new()),
+            permissions: Arc::new(Vec::new()),
+        }
+    }
+
+    pub fn stdin(self, file: Box<dyn WasiFile>) -> Self {
+        self.ctx.set_stdin(file);
+        self
+    }
+
+    pub fn stdout(self, file: Box<dyn WasiFile>) -> Self {
+        self.ctx.set_stdout(file);
+        self
+    }
+
+    pub fn stderr(self, file: Box<dyn WasiFile>) -> Self {
+        self.ctx.set_stderr(file);
+        self
+    }
+
+    /// Scope every preopen added from here on to the given allowlist: a
+    /// `preopened_dir` or `preopen_socket` call made afterwards wraps its
+    /// argument in a [`PermissionedDir`]/[`PermissionedFile`] that checks
+    /// paths and accepted connections against `permissions` before
+    /// delegating. Manifest-driven embedders (e.g. Blockless) call this
+    /// once with a run's [`crate::BlocklessConfig::permissions`]. An empty
+    /// `permissions` list turns the wrapping off: preopens added afterwards
+    /// are not gated at all.
+    pub fn permissions(mut self, permissions: Vec<Permission>) -> Self {
+        self.permissions = Arc::new(permissions);
+        self
+    }
+
+    /// `path` is also the root [`Permission`] paths are resolved against for
+    /// this preopen - see [`PermissionedDir`] - so it must be the absolute
+    /// host path `dir` was opened at, not a guest-facing alias.
+    pub fn preopened_dir(
+        self,
+        dir: Box<dyn WasiDir>,
+        path: impl AsRef<std::path::Path>,
+    ) -> Result<Self, Error> {
+        let dir: Box<dyn WasiDir> = if self.permissions.is_empty() {
+            dir
+        } else {
+            Box::new(PermissionedDir::new(
+                dir,
+                self.permissions.clone(),
+                path.as_ref(),
+            ))
+        };
+        self.ctx.push_preopened_dir(dir, path)?;
+        Ok(self)
+    }
+
+    /// Hand the guest a bound/listening `TcpListener` as a preopened fd.
+    /// Connections it accepts come back to the guest as new fds via
+    /// `sock_accept`, which the snapshot implementations route to the
+    /// [`WasiFile::sock_accept`] of the table entry found at `fd` (here
+    /// [`crate::socket::WasiListener::sock_accept`], or the wrapping
+    /// [`PermissionedFile`]'s). If [`Self::permissions`] has been set,
+    /// accepted connections are checked against it; see
+    /// [`PermissionedFile::sock_accept`].
+    pub fn preopen_socket(self, fd: u32, listener: std::net::TcpListener) -> Result<Self, Error> {
+        let socket = crate::socket::WasiListener::from_std(listener)?;
+        let file: Box<dyn WasiFile> = if self.permissions.is_empty() {
+            Box::new(socket)
+        } else {
+            Box::new(PermissionedFile::new(
+                Box::new(socket),
+                self.permissions.clone(),
+            ))
+        };
+        self.ctx.insert_file(fd, file);
+        Ok(self)
+    }
+
+    pub fn build(self) -> WasiCtx {
+        self.ctx
+    }
+}
@@ -0,0 +1,76 @@
+use crate::file::{FdFlags, FileType, Filestat, OFlags, WasiFile};
+use crate::{Error, SystemTimeSpec};
+use std::any::Any;
+use std::path::PathBuf;
+
+/// A WASI directory. `wasi-common` itself provides no implementation of
+/// this trait backed by a real directory - its only one is the
+/// `permissions::PermissionedDir` wrapper around another `WasiDir`. See the
+/// crate-level docs for where implementations live.
+#[async_trait::async_trait]
+pub trait WasiDir: Send + Sync {
+    fn as_any(&self) -> &dyn Any;
+
+    async fn open_file(
+        &self,
+        symlink_follow: bool,
+        path: &str,
+        oflags: OFlags,
+        read: bool,
+        write: bool,
+        fdflags: FdFlags,
+    ) -> Result<OpenResult, Error>;
+
+    async fn open_dir(&self, symlink_follow: bool, path: &str) -> Result<Box<dyn WasiDir>, Error>;
+
+    async fn create_dir(&self, path: &str) -> Result<(), Error>;
+
+    async fn readdir(
+        &self,
+        cursor: ReaddirCursor,
+    ) -> Result<Box<dyn Iterator<Item = Result<ReaddirEntity, Error>> + Send>, Error>;
+
+    async fn symlink(&self, old_path: &str, new_path: &str) -> Result<(), Error>;
+    async fn remove_dir(&self, path: &str) -> Result<(), Error>;
+    async fn unlink_file(&self, path: &str) -> Result<(), Error>;
+    async fn read_link(&self, path: &str) -> Result<PathBuf, Error>;
+    async fn get_filestat(&self) -> Result<Filestat, Error>;
+    async fn get_path_filestat(&self, path: &str, follow_symlinks: bool) -> Result<Filestat, Error>;
+    async fn rename(&self, path: &str, dest_dir: &dyn WasiDir, dest_path: &str) -> Result<(), Error>;
+    async fn hard_link(&self, path: &str, target_dir: &dyn WasiDir, target_path: &str) -> Result<(), Error>;
+
+    async fn set_times(
+        &self,
+        path: &str,
+        atime: Option<SystemTimeSpec>,
+        mtime: Option<SystemTimeSpec>,
+        follow_symlinks: bool,
+    ) -> Result<(), Error>;
+}
+
+pub enum OpenResult {
+    File(Box<dyn WasiFile>),
+    Dir(Box<dyn WasiDir>),
+}
+
+#[derive(Debug, Copy, Clone, Default)]
+pub struct ReaddirCursor(u64);
+
+impl From<u64> for ReaddirCursor {
+    fn from(c: u64) -> Self {
+        ReaddirCursor(c)
+    }
+}
+
+impl From<ReaddirCursor> for u64 {
+    fn from(c: ReaddirCursor) -> u64 {
+        c.0
+    }
+}
+
+pub struct ReaddirEntity {
+    pub next: ReaddirCursor,
+    pub inode: u64,
+    pub name: String,
+    pub filetype: FileType,
+}
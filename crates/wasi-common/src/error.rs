@@ -0,0 +1,187 @@
+use std::fmt;
+use std::io;
+use std::num::TryFromIntError;
+use std::str::Utf8Error;
+
+pub use wiggle::GuestError;
+
+/// An error returned from the `wasi-common` implementation.
+///
+/// This is a wrapper around an `anyhow::Error` (the originating error, or
+/// a message from one of the [`ErrorExt`] constructors) that callers can
+/// downcast, map back to host-level diagnostics, or re-raise as a trap.
+#[derive(Debug)]
+pub struct Error {
+    inner: anyhow::Error,
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.inner.fmt(f)
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        self.inner.source()
+    }
+}
+
+impl Error {
+    pub fn trap(inner: anyhow::Error) -> Self {
+        Self { inner }
+    }
+
+    pub fn into_anyhow(self) -> anyhow::Error {
+        self.inner
+    }
+
+    pub fn downcast<E: std::error::Error + Send + Sync + 'static>(self) -> Result<E, Self> {
+        self.inner.downcast().map_err(Error::trap)
+    }
+
+    pub fn downcast_ref<E: std::error::Error + Send + Sync + 'static>(&self) -> Option<&E> {
+        self.inner.downcast_ref()
+    }
+
+    pub fn is<E: std::error::Error + Send + Sync + 'static>(&self) -> bool {
+        self.inner.is::<E>()
+    }
+
+    pub fn context(self, s: impl Into<String>) -> Self {
+        Self {
+            inner: self.inner.context(s.into()),
+        }
+    }
+}
+
+/// Constructors used throughout `wasi-common` for its errno-style error
+/// conditions, each producing an [`Error`] that carries a human-readable
+/// message for that condition.
+pub trait ErrorExt {
+    fn not_found() -> Self;
+    fn too_big() -> Self;
+    fn badf() -> Self;
+    fn exist() -> Self;
+    fn illegal_byte_sequence() -> Self;
+    fn invalid_argument() -> Self;
+    fn io() -> Self;
+    fn name_too_long() -> Self;
+    fn not_capable() -> Self;
+    fn not_dir() -> Self;
+    fn not_supported() -> Self;
+    fn overflow() -> Self;
+    fn range() -> Self;
+    fn seek_pipe() -> Self;
+    fn perm() -> Self;
+    /// The operation would block on a nonblocking resource (e.g. a
+    /// nonblocking `accept` with no pending connection). Distinct from the
+    /// other constructors here in that it's expected to be recoverable: a
+    /// caller driving a poll-then-act loop should retry instead of treating
+    /// it as a trap.
+    fn would_block() -> Self;
+}
+
+impl ErrorExt for Error {
+    fn not_found() -> Self {
+        Self::trap(anyhow::anyhow!("not found"))
+    }
+    fn too_big() -> Self {
+        Self::trap(anyhow::anyhow!("too big"))
+    }
+    fn badf() -> Self {
+        Self::trap(anyhow::anyhow!("bad file descriptor"))
+    }
+    fn exist() -> Self {
+        Self::trap(anyhow::anyhow!("file exists"))
+    }
+    fn illegal_byte_sequence() -> Self {
+        Self::trap(anyhow::anyhow!("illegal byte sequence"))
+    }
+    fn invalid_argument() -> Self {
+        Self::trap(anyhow::anyhow!("invalid argument"))
+    }
+    fn io() -> Self {
+        Self::trap(anyhow::anyhow!("i/o error"))
+    }
+    fn name_too_long() -> Self {
+        Self::trap(anyhow::anyhow!("name too long"))
+    }
+    fn not_capable() -> Self {
+        Self::trap(anyhow::anyhow!("capabilities insufficient"))
+    }
+    fn not_dir() -> Self {
+        Self::trap(anyhow::anyhow!("not a directory"))
+    }
+    fn not_supported() -> Self {
+        Self::trap(anyhow::anyhow!("not supported"))
+    }
+    fn overflow() -> Self {
+        Self::trap(anyhow::anyhow!("overflow"))
+    }
+    fn range() -> Self {
+        Self::trap(anyhow::anyhow!("out of range"))
+    }
+    fn seek_pipe() -> Self {
+        Self::trap(anyhow::anyhow!("cannot seek on a pipe"))
+    }
+    fn perm() -> Self {
+        Self::trap(anyhow::anyhow!("permission denied"))
+    }
+    fn would_block() -> Self {
+        Self::trap(anyhow::anyhow!("operation would block"))
+    }
+}
+
+impl From<io::Error> for Error {
+    fn from(e: io::Error) -> Self {
+        Self::trap(e.into())
+    }
+}
+
+impl From<Utf8Error> for Error {
+    fn from(e: Utf8Error) -> Self {
+        Self::trap(e.into())
+    }
+}
+
+impl From<TryFromIntError> for Error {
+    fn from(e: TryFromIntError) -> Self {
+        Self::trap(e.into())
+    }
+}
+
+impl From<GuestError> for Error {
+    fn from(e: GuestError) -> Self {
+        Self::trap(e.into())
+    }
+}
+
+/// Typed error for `proc_exit`, representing a guest-requested exit code.
+///
+/// Under the default [`crate::ExitBehavior::TrapAndUnwind`], a `proc_exit`
+/// hostcall does not call `std::process::exit`: it returns an `I32Exit`
+/// error, which unwinds the wasm call stack as an ordinary trap (only
+/// [`crate::ExitBehavior::Terminate`] exits the process directly). It is up
+/// to the embedder to
+/// downcast the resulting `anyhow::Error` back to an `I32Exit` (see
+/// [`crate::maybe_exit_on_error`] and [`crate::i32_exit_status`]) and decide
+/// what "exiting" means for their process: the Wasmtime CLI terminates the
+/// whole process, while a multi-tenant embedder can tear down just the one
+/// guest instance and keep serving others.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct I32Exit(pub i32);
+
+impl fmt::Display for I32Exit {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Exited with i32 exit status {}", self.0)
+    }
+}
+
+impl std::error::Error for I32Exit {}
+
+impl From<I32Exit> for Error {
+    fn from(e: I32Exit) -> Self {
+        Self::trap(e.into())
+    }
+}
@@ -0,0 +1,136 @@
+use crate::{Error, ErrorExt};
+use bitflags::bitflags;
+use cap_std::time::SystemTime;
+use std::any::Any;
+use std::io::{IoSlice, IoSliceMut};
+
+/// A WASI file. Besides the `pipe::{ReadPipe, WritePipe, OutputFile}`
+/// wrappers around `std::io::{Read, Write}`, the `socket` resources, and the
+/// `permissions::PermissionedFile` wrapper, real filesystem access is
+/// provided by `wasi_common::sync` (cap-std backed) or `wasi_common::tokio`.
+#[async_trait::async_trait]
+pub trait WasiFile: Send + Sync {
+    fn as_any(&self) -> &dyn Any;
+
+    /// Return the file type, as it should be reported to the guest via
+    /// `fd_filestat_get`/`fd_fdstat_get`.
+    async fn get_filetype(&self) -> Result<FileType, Error>;
+
+    /// Whether this file refers to a terminal. Defaults to `false`.
+    async fn isatty(&self) -> bool {
+        false
+    }
+
+    async fn sock_accept(&self, _fdflags: FdFlags) -> Result<Box<dyn WasiFile>, Error> {
+        Err(Error::badf())
+    }
+
+    async fn datasync(&self) -> Result<(), Error> {
+        Ok(())
+    }
+
+    async fn sync(&self) -> Result<(), Error> {
+        Ok(())
+    }
+
+    async fn get_fdflags(&self) -> Result<FdFlags, Error> {
+        Ok(FdFlags::empty())
+    }
+
+    async fn set_fdflags(&mut self, _flags: FdFlags) -> Result<(), Error> {
+        Err(Error::not_supported())
+    }
+
+    async fn get_filestat(&self) -> Result<Filestat, Error> {
+        Err(Error::not_supported())
+    }
+
+    async fn set_filestat_size(&self, _size: u64) -> Result<(), Error> {
+        Err(Error::not_supported())
+    }
+
+    /// Extend the file as needed to reserve `len` bytes
+    /// starting at `offset`, without necessarily writing zeroes (backed by
+    /// `posix_fallocate`/`F_PREALLOCATE`-style syscalls where available).
+    /// Implementations that cannot support this (pipes, sockets, virtual
+    /// filesystems) should keep the default of `ENOTSUP`.
+    async fn allocate(&self, _offset: u64, _len: u64) -> Result<(), Error> {
+        Err(Error::not_supported())
+    }
+
+    async fn set_times(
+        &self,
+        _atime: Option<crate::SystemTimeSpec>,
+        _mtime: Option<crate::SystemTimeSpec>,
+    ) -> Result<(), Error> {
+        Err(Error::not_supported())
+    }
+
+    async fn read_vectored<'a>(&self, bufs: &mut [IoSliceMut<'a>]) -> Result<u64, Error>;
+    async fn read_vectored_at<'a>(
+        &self,
+        bufs: &mut [IoSliceMut<'a>],
+        offset: u64,
+    ) -> Result<u64, Error>;
+    async fn write_vectored<'a>(&self, bufs: &[IoSlice<'a>]) -> Result<u64, Error>;
+    async fn write_vectored_at<'a>(&self, bufs: &[IoSlice<'a>], offset: u64) -> Result<u64, Error>;
+    async fn seek(&self, pos: std::io::SeekFrom) -> Result<u64, Error>;
+    async fn peek(&self, buf: &mut [u8]) -> Result<u64, Error>;
+    fn num_ready_bytes(&self) -> Result<u64, Error> {
+        Ok(1)
+    }
+
+    /// Whether this file is ready for read/write without suspending. Used
+    /// by `poll_oneoff` implementations to shortcut the general scheduler
+    /// readiness path for files that are always ready (e.g.
+    /// [`crate::pipe::OutputFile`]). Defaults to `false`.
+    fn always_ready(&self) -> bool {
+        false
+    }
+}
+
+bitflags! {
+    #[derive(Default)]
+    pub struct FdFlags: u32 {
+        const APPEND   = 0b1;
+        const DSYNC    = 0b10;
+        const NONBLOCK = 0b100;
+        const RSYNC    = 0b1000;
+        const SYNC     = 0b10000;
+    }
+}
+
+bitflags! {
+    #[derive(Default)]
+    pub struct OFlags: u32 {
+        const CREATE    = 0b1;
+        const DIRECTORY = 0b10;
+        const EXCLUSIVE = 0b100;
+        const TRUNCATE  = 0b1000;
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum FileType {
+    Unknown,
+    BlockDevice,
+    CharacterDevice,
+    Directory,
+    RegularFile,
+    SocketDgram,
+    SocketStream,
+    SymbolicLink,
+    Pipe,
+}
+
+#[derive(Debug, Copy, Clone)]
+pub struct Filestat {
+    pub device_id: u64,
+    pub inode: u64,
+    pub filetype: FileType,
+    pub nlink: u64,
+    pub size: u64,
+    pub atim: Option<SystemTime>,
+    pub mtim: Option<SystemTime>,
+    pub ctim: Option<SystemTime>,
+}
@@ -75,10 +75,12 @@ mod ctx;
 pub mod dir;
 mod error;
 pub mod file;
+pub mod permissions;
 pub mod pipe;
 pub mod random;
 pub mod sched;
 pub mod snapshots;
+pub mod socket;
 mod string_array;
 #[cfg_attr(docsrs, doc(cfg(feature = "sync")))]
 #[cfg(feature = "sync")]
@@ -90,7 +92,7 @@ pub mod tokio;
 
 pub use cap_rand::RngCore;
 pub use clocks::{SystemTimeSpec, WasiClocks, WasiMonotonicClock, WasiSystemClock};
-pub use ctx::WasiCtx;
+pub use ctx::{ExitBehavior, WasiCtx, WasiCtxBuilder};
 pub use dir::WasiDir;
 pub use error::{Error, ErrorExt, I32Exit};
 pub use file::WasiFile;
@@ -99,6 +101,9 @@ pub use string_array::{StringArray, StringArrayError};
 pub use table::Table;
 
 mod blockless;
+#[cfg_attr(docsrs, doc(cfg(feature = "wasmtime")))]
+#[cfg(feature = "wasmtime")]
+pub use blockless::run;
 pub use blockless::{
     BlocklessConfig, BlocklessConfigVersion, BlocklessModule, DriverConfig, LoggerLevel,
     ModuleType, Permission, Stderr, Stdout,
@@ -197,3 +202,21 @@ pub fn maybe_exit_on_error(e: anyhow::Error) -> anyhow::Error {
 
     e
 }
+
+/// Downcast an `anyhow::Error` produced by a guest `call` to the exit code
+/// a guest requested via `proc_exit`, without ever calling
+/// `std::process::exit`.
+///
+/// This is the multi-tenant counterpart to [`maybe_exit_on_error`]: where
+/// that function is only suitable when it's fine for a WASI failure to
+/// terminate the whole host process (e.g. the Wasmtime CLI), this one lets
+/// an embedder running many guests in one process collect a single guest's
+/// exit code and keep serving the others. It only succeeds if `e` is an
+/// [`I32Exit`] - any other error (including a `Trap`) is handed back
+/// unchanged so the caller can decide how to report it.
+pub fn i32_exit_status(e: anyhow::Error) -> Result<i32, anyhow::Error> {
+    match e.downcast::<I32Exit>() {
+        Ok(I32Exit(code)) => Ok(code),
+        Err(e) => Err(e),
+    }
+}
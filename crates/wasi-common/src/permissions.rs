@@ -0,0 +1,359 @@
+//! Capability-scoped `WasiDir`/`WasiFile` wrappers, consulting a
+//! [`Permission`] allowlist before delegating any operation that touches a
+//! path or a network address. `WasiCtxBuilder::permissions` wraps every
+//! preopen in these automatically, so an embedder gets manifest-level
+//! sandboxing without writing its own `WasiDir`/`WasiFile` impls.
+//!
+//! `Permission` paths are absolute host paths, while the WASI `path`
+//! argument handed to a `WasiDir` method is always relative to that
+//! directory's preopen. `PermissionedDir` resolves the latter against the
+//! former before checking, so it has to know the absolute path it (or its
+//! parent) was preopened at - see `PermissionedDir::new`.
+
+use crate::dir::{OpenResult, ReaddirCursor, ReaddirEntity, WasiDir};
+use crate::file::{FdFlags, FileType, Filestat, OFlags, WasiFile};
+use crate::socket::WasiTcpSocket;
+use crate::{Error, ErrorExt, Permission, SystemTimeSpec};
+use std::any::Any;
+use std::io::{IoSlice, IoSliceMut, SeekFrom};
+use std::path::{Component, Path, PathBuf};
+use std::sync::Arc;
+
+fn check(permissions: &[Permission], schema: &str, target: &str) -> Result<(), Error> {
+    if permissions.iter().any(|p| p.allows(schema, target)) {
+        Ok(())
+    } else {
+        Err(Error::not_capable())
+    }
+}
+
+/// Lexically resolve `path` against `root`, collapsing `.`/`..` components
+/// instead of just concatenating them the way `root.join(path)` would.
+/// Plain concatenation lets a `..` component in `path` walk back out above
+/// `root` while still reading as a sub-path of it by raw segment
+/// comparison (e.g. `root.join("a/../../secret")` under `Permission::allows`'s
+/// segment matching) - this rejects any `path` that would resolve above
+/// `root` instead.
+fn resolve(root: &Path, path: &str) -> Result<PathBuf, Error> {
+    let mut resolved = root.to_path_buf();
+    for component in Path::new(path).components() {
+        match component {
+            Component::Normal(part) => resolved.push(part),
+            Component::CurDir => {}
+            Component::ParentDir => {
+                if resolved == root {
+                    return Err(Error::not_capable());
+                }
+                resolved.pop();
+            }
+            Component::RootDir | Component::Prefix(_) => return Err(Error::not_capable()),
+        }
+    }
+    Ok(resolved)
+}
+
+/// A [`WasiDir`] that checks every path-taking operation against a
+/// `"file"`-schema [`Permission`] before delegating to `inner`. Any
+/// sub-directory or file it opens stays gated, wrapped in another
+/// `PermissionedDir`/[`PermissionedFile`] with the same allowlist.
+pub struct PermissionedDir {
+    inner: Box<dyn WasiDir>,
+    permissions: Arc<Vec<Permission>>,
+    /// Absolute host path this directory was opened at. WASI hands every
+    /// operation a path *relative to this directory* (e.g. `config/app.toml`),
+    /// but manifest permissions are written as absolute paths (`/var/data`),
+    /// so `check` resolves the former against this root (see `resolve`)
+    /// before asking [`Permission::allows`].
+    root: PathBuf,
+}
+
+impl PermissionedDir {
+    /// `root` is the absolute path `inner` was preopened/opened at - for the
+    /// top-level preopen this is the path passed to
+    /// [`crate::WasiCtxBuilder::preopened_dir`]; for a sub-directory it's
+    /// `root.join(path)` of the `PermissionedDir` it was opened through.
+    pub fn new(
+        inner: Box<dyn WasiDir>,
+        permissions: Arc<Vec<Permission>>,
+        root: impl Into<PathBuf>,
+    ) -> Self {
+        Self {
+            inner,
+            permissions,
+            root: root.into(),
+        }
+    }
+
+    fn check(&self, path: &str) -> Result<(), Error> {
+        let resolved = resolve(&self.root, path)?;
+        check(&self.permissions, "file", &resolved.to_string_lossy())
+    }
+}
+
+#[async_trait::async_trait]
+impl WasiDir for PermissionedDir {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    async fn open_file(
+        &self,
+        symlink_follow: bool,
+        path: &str,
+        oflags: OFlags,
+        read: bool,
+        write: bool,
+        fdflags: FdFlags,
+    ) -> Result<OpenResult, Error> {
+        self.check(path)?;
+        Ok(
+            match self
+                .inner
+                .open_file(symlink_follow, path, oflags, read, write, fdflags)
+                .await?
+            {
+                OpenResult::File(f) => {
+                    OpenResult::File(Box::new(PermissionedFile::new(f, self.permissions.clone())))
+                }
+                OpenResult::Dir(d) => OpenResult::Dir(Box::new(PermissionedDir::new(
+                    d,
+                    self.permissions.clone(),
+                    self.root.join(path),
+                ))),
+            },
+        )
+    }
+
+    async fn open_dir(&self, symlink_follow: bool, path: &str) -> Result<Box<dyn WasiDir>, Error> {
+        self.check(path)?;
+        let dir = self.inner.open_dir(symlink_follow, path).await?;
+        Ok(Box::new(PermissionedDir::new(
+            dir,
+            self.permissions.clone(),
+            self.root.join(path),
+        )))
+    }
+
+    async fn create_dir(&self, path: &str) -> Result<(), Error> {
+        self.check(path)?;
+        self.inner.create_dir(path).await
+    }
+
+    async fn readdir(
+        &self,
+        cursor: ReaddirCursor,
+    ) -> Result<Box<dyn Iterator<Item = Result<ReaddirEntity, Error>> + Send>, Error> {
+        self.inner.readdir(cursor).await
+    }
+
+    async fn symlink(&self, old_path: &str, new_path: &str) -> Result<(), Error> {
+        self.check(old_path)?;
+        self.check(new_path)?;
+        self.inner.symlink(old_path, new_path).await
+    }
+
+    async fn remove_dir(&self, path: &str) -> Result<(), Error> {
+        self.check(path)?;
+        self.inner.remove_dir(path).await
+    }
+
+    async fn unlink_file(&self, path: &str) -> Result<(), Error> {
+        self.check(path)?;
+        self.inner.unlink_file(path).await
+    }
+
+    async fn read_link(&self, path: &str) -> Result<PathBuf, Error> {
+        self.check(path)?;
+        self.inner.read_link(path).await
+    }
+
+    async fn get_filestat(&self) -> Result<Filestat, Error> {
+        self.inner.get_filestat().await
+    }
+
+    async fn get_path_filestat(
+        &self,
+        path: &str,
+        follow_symlinks: bool,
+    ) -> Result<Filestat, Error> {
+        self.check(path)?;
+        self.inner.get_path_filestat(path, follow_symlinks).await
+    }
+
+    async fn rename(
+        &self,
+        path: &str,
+        dest_dir: &dyn WasiDir,
+        dest_path: &str,
+    ) -> Result<(), Error> {
+        self.check(path)?;
+        if let Some(dest) = dest_dir.as_any().downcast_ref::<PermissionedDir>() {
+            dest.check(dest_path)?;
+        }
+        self.inner.rename(path, dest_dir, dest_path).await
+    }
+
+    async fn hard_link(
+        &self,
+        path: &str,
+        target_dir: &dyn WasiDir,
+        target_path: &str,
+    ) -> Result<(), Error> {
+        self.check(path)?;
+        if let Some(target) = target_dir.as_any().downcast_ref::<PermissionedDir>() {
+            target.check(target_path)?;
+        }
+        self.inner.hard_link(path, target_dir, target_path).await
+    }
+
+    async fn set_times(
+        &self,
+        path: &str,
+        atime: Option<SystemTimeSpec>,
+        mtime: Option<SystemTimeSpec>,
+        follow_symlinks: bool,
+    ) -> Result<(), Error> {
+        self.check(path)?;
+        self.inner
+            .set_times(path, atime, mtime, follow_symlinks)
+            .await
+    }
+}
+
+/// A [`WasiFile`] that otherwise just delegates to `inner`, but checks an
+/// accepted connection's peer address against a `"tcp"`-schema [`Permission`]
+/// before handing it back, so a guest can't use an allowed listener to reach
+/// an address it isn't permitted to talk to.
+pub struct PermissionedFile {
+    inner: Box<dyn WasiFile>,
+    permissions: Arc<Vec<Permission>>,
+}
+
+impl PermissionedFile {
+    pub fn new(inner: Box<dyn WasiFile>, permissions: Arc<Vec<Permission>>) -> Self {
+        Self { inner, permissions }
+    }
+}
+
+#[async_trait::async_trait]
+impl WasiFile for PermissionedFile {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    async fn get_filetype(&self) -> Result<FileType, Error> {
+        self.inner.get_filetype().await
+    }
+
+    async fn isatty(&self) -> bool {
+        self.inner.isatty().await
+    }
+
+    async fn sock_accept(&self, fdflags: FdFlags) -> Result<Box<dyn WasiFile>, Error> {
+        let accepted = self.inner.sock_accept(fdflags).await?;
+        let addr = accepted
+            .as_any()
+            .downcast_ref::<WasiTcpSocket>()
+            .and_then(WasiTcpSocket::peer_addr)
+            .ok_or_else(Error::not_capable)?;
+        check(&self.permissions, "tcp", &addr.to_string())?;
+        Ok(Box::new(PermissionedFile::new(
+            accepted,
+            self.permissions.clone(),
+        )))
+    }
+
+    async fn datasync(&self) -> Result<(), Error> {
+        self.inner.datasync().await
+    }
+
+    async fn sync(&self) -> Result<(), Error> {
+        self.inner.sync().await
+    }
+
+    async fn get_fdflags(&self) -> Result<FdFlags, Error> {
+        self.inner.get_fdflags().await
+    }
+
+    async fn set_fdflags(&mut self, flags: FdFlags) -> Result<(), Error> {
+        self.inner.set_fdflags(flags).await
+    }
+
+    async fn get_filestat(&self) -> Result<Filestat, Error> {
+        self.inner.get_filestat().await
+    }
+
+    async fn set_filestat_size(&self, size: u64) -> Result<(), Error> {
+        self.inner.set_filestat_size(size).await
+    }
+
+    async fn allocate(&self, offset: u64, len: u64) -> Result<(), Error> {
+        self.inner.allocate(offset, len).await
+    }
+
+    async fn set_times(
+        &self,
+        atime: Option<SystemTimeSpec>,
+        mtime: Option<SystemTimeSpec>,
+    ) -> Result<(), Error> {
+        self.inner.set_times(atime, mtime).await
+    }
+
+    async fn read_vectored<'a>(&self, bufs: &mut [IoSliceMut<'a>]) -> Result<u64, Error> {
+        self.inner.read_vectored(bufs).await
+    }
+
+    async fn read_vectored_at<'a>(
+        &self,
+        bufs: &mut [IoSliceMut<'a>],
+        offset: u64,
+    ) -> Result<u64, Error> {
+        self.inner.read_vectored_at(bufs, offset).await
+    }
+
+    async fn write_vectored<'a>(&self, bufs: &[IoSlice<'a>]) -> Result<u64, Error> {
+        self.inner.write_vectored(bufs).await
+    }
+
+    async fn write_vectored_at<'a>(&self, bufs: &[IoSlice<'a>], offset: u64) -> Result<u64, Error> {
+        self.inner.write_vectored_at(bufs, offset).await
+    }
+
+    async fn seek(&self, pos: SeekFrom) -> Result<u64, Error> {
+        self.inner.seek(pos).await
+    }
+
+    async fn peek(&self, buf: &mut [u8]) -> Result<u64, Error> {
+        self.inner.peek(buf).await
+    }
+
+    fn num_ready_bytes(&self) -> Result<u64, Error> {
+        self.inner.num_ready_bytes()
+    }
+
+    fn always_ready(&self) -> bool {
+        self.inner.always_ready()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn traversal_out_of_an_allowed_subdir_is_denied() {
+        let permissions = vec![Permission {
+            schema: "file".to_string(),
+            url: "/root/data".to_string(),
+        }];
+
+        let resolved = resolve(Path::new("/root"), "data/../secret").unwrap();
+        assert_eq!(resolved, Path::new("/root/secret"));
+        assert!(check(&permissions, "file", &resolved.to_string_lossy()).is_err());
+    }
+
+    #[test]
+    fn traversal_above_the_preopen_root_is_rejected_outright() {
+        assert!(resolve(Path::new("/root/data"), "../../etc/passwd").is_err());
+    }
+}
@@ -0,0 +1,178 @@
+//! Implementations of `WasiFile` over `std::io::{Read, Write}`: the virtual
+//! `ReadPipe`/`WritePipe` pipe ends, and `OutputFile`, a write-only stdio
+//! fast path that `poll_oneoff` treats as always ready.
+
+use crate::file::{FileType, WasiFile};
+use crate::{Error, ErrorExt};
+use std::any::Any;
+use std::io::{self, IoSlice, IoSliceMut, Read, Write};
+use std::sync::{Arc, RwLock};
+
+/// A virtual pipe read end, reading from any `std::io::Read` source.
+#[derive(Clone)]
+pub struct ReadPipe<R> {
+    reader: Arc<RwLock<R>>,
+}
+
+impl<R: Read> ReadPipe<R> {
+    pub fn new(r: R) -> Self {
+        Self {
+            reader: Arc::new(RwLock::new(r)),
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl<R: Read + Send + Sync + 'static> WasiFile for ReadPipe<R> {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+    async fn get_filetype(&self) -> Result<FileType, Error> {
+        Ok(FileType::Pipe)
+    }
+    async fn read_vectored<'a>(&self, bufs: &mut [IoSliceMut<'a>]) -> Result<u64, Error> {
+        let n = self.reader.write().unwrap().read_vectored(bufs)?;
+        Ok(n as u64)
+    }
+    async fn read_vectored_at<'a>(
+        &self,
+        _bufs: &mut [IoSliceMut<'a>],
+        _offset: u64,
+    ) -> Result<u64, Error> {
+        Err(Error::seek_pipe())
+    }
+    async fn write_vectored<'a>(&self, _bufs: &[IoSlice<'a>]) -> Result<u64, Error> {
+        Err(Error::badf())
+    }
+    async fn write_vectored_at<'a>(
+        &self,
+        _bufs: &[IoSlice<'a>],
+        _offset: u64,
+    ) -> Result<u64, Error> {
+        Err(Error::seek_pipe())
+    }
+    async fn seek(&self, _pos: io::SeekFrom) -> Result<u64, Error> {
+        Err(Error::seek_pipe())
+    }
+    async fn peek(&self, _buf: &mut [u8]) -> Result<u64, Error> {
+        Ok(0)
+    }
+}
+
+/// A virtual pipe write end, writing to any `std::io::Write` sink.
+#[derive(Clone)]
+pub struct WritePipe<W> {
+    writer: Arc<RwLock<W>>,
+}
+
+impl<W: Write> WritePipe<W> {
+    pub fn new(w: W) -> Self {
+        Self {
+            writer: Arc::new(RwLock::new(w)),
+        }
+    }
+
+    pub fn try_into_inner(self) -> Result<W, Self> {
+        match Arc::try_unwrap(self.writer) {
+            Ok(lock) => Ok(lock.into_inner().unwrap()),
+            Err(writer) => Err(Self { writer }),
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl<W: Write + Send + Sync + 'static> WasiFile for WritePipe<W> {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+    async fn get_filetype(&self) -> Result<FileType, Error> {
+        Ok(FileType::Pipe)
+    }
+    async fn read_vectored<'a>(&self, _bufs: &mut [IoSliceMut<'a>]) -> Result<u64, Error> {
+        Err(Error::badf())
+    }
+    async fn read_vectored_at<'a>(
+        &self,
+        _bufs: &mut [IoSliceMut<'a>],
+        _offset: u64,
+    ) -> Result<u64, Error> {
+        Err(Error::seek_pipe())
+    }
+    async fn write_vectored<'a>(&self, bufs: &[IoSlice<'a>]) -> Result<u64, Error> {
+        let n = self.writer.write().unwrap().write_vectored(bufs)?;
+        Ok(n as u64)
+    }
+    async fn write_vectored_at<'a>(
+        &self,
+        _bufs: &[IoSlice<'a>],
+        _offset: u64,
+    ) -> Result<u64, Error> {
+        Err(Error::seek_pipe())
+    }
+    async fn seek(&self, _pos: io::SeekFrom) -> Result<u64, Error> {
+        Err(Error::seek_pipe())
+    }
+    async fn peek(&self, _buf: &mut [u8]) -> Result<u64, Error> {
+        Ok(0)
+    }
+}
+
+/// A write-only `WasiFile` over any `std::io::Write` - typically a real OS
+/// handle (a file, or the inherited terminal), or `std::io::sink()` for
+/// discarded output - performing blocking writes directly instead of
+/// going through `WritePipe`'s poll-to-ready machinery. `always_ready`
+/// tells `poll_oneoff` it can skip straight to "ready" for this fd, so a
+/// guest writing to, say, a log file never suspends waiting to be
+/// scheduled.
+#[derive(Clone)]
+pub struct OutputFile<W> {
+    writer: Arc<RwLock<W>>,
+}
+
+impl<W: Write> OutputFile<W> {
+    pub fn new(w: W) -> Self {
+        Self {
+            writer: Arc::new(RwLock::new(w)),
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl<W: Write + Send + Sync + 'static> WasiFile for OutputFile<W> {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+    async fn get_filetype(&self) -> Result<FileType, Error> {
+        Ok(FileType::RegularFile)
+    }
+    async fn read_vectored<'a>(&self, _bufs: &mut [IoSliceMut<'a>]) -> Result<u64, Error> {
+        Err(Error::badf())
+    }
+    async fn read_vectored_at<'a>(
+        &self,
+        _bufs: &mut [IoSliceMut<'a>],
+        _offset: u64,
+    ) -> Result<u64, Error> {
+        Err(Error::seek_pipe())
+    }
+    async fn write_vectored<'a>(&self, bufs: &[IoSlice<'a>]) -> Result<u64, Error> {
+        let n = self.writer.write().unwrap().write_vectored(bufs)?;
+        Ok(n as u64)
+    }
+    async fn write_vectored_at<'a>(
+        &self,
+        _bufs: &[IoSlice<'a>],
+        _offset: u64,
+    ) -> Result<u64, Error> {
+        Err(Error::seek_pipe())
+    }
+    async fn seek(&self, _pos: io::SeekFrom) -> Result<u64, Error> {
+        Err(Error::seek_pipe())
+    }
+    async fn peek(&self, _buf: &mut [u8]) -> Result<u64, Error> {
+        Ok(0)
+    }
+    fn always_ready(&self) -> bool {
+        true
+    }
+}
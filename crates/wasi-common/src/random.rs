@@ -0,0 +1,38 @@
+pub use cap_rand::RngCore;
+
+/// A `RngCore` impl that is deterministic, for use in contexts where
+/// reproducibility matters more than unpredictability (e.g. tests, or
+/// embedders who explicitly opt out of host randomness).
+pub struct Deterministic {
+    state: u64,
+}
+
+impl Deterministic {
+    pub fn new(seed: u64) -> Self {
+        Deterministic { state: seed }
+    }
+}
+
+impl RngCore for Deterministic {
+    fn next_u32(&mut self) -> u32 {
+        (self.next_u64() >> 32) as u32
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        // A small xorshift64 step (shifts 13/7/17) with the xorshift64*
+        // output multiplier: not cryptographically secure, but
+        // deterministic and cheap, which is exactly what callers of this
+        // type are asking for.
+        self.state ^= self.state << 13;
+        self.state ^= self.state >> 7;
+        self.state ^= self.state << 17;
+        self.state.wrapping_mul(0x2545_f491_4f6c_dd1d)
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        for chunk in dest.chunks_mut(8) {
+            let bytes = self.next_u64().to_le_bytes();
+            chunk.copy_from_slice(&bytes[..chunk.len()]);
+        }
+    }
+}
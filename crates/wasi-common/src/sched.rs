@@ -0,0 +1,89 @@
+use crate::file::WasiFile;
+use crate::Error;
+use cap_std::time::Duration;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+/// An entry in a [`Poll`] set: either a subscription to readiness on a file,
+/// or a subscription to a clock timeout.
+pub enum Subscription<'a> {
+    Read(FileReadiness<'a>),
+    Write(FileReadiness<'a>),
+    MonotonicClock {
+        timeout: Duration,
+        result: &'a AtomicBool,
+    },
+}
+
+/// A file registered for readiness polling, along with a place to record
+/// whether it turned out to be ready.
+pub struct FileReadiness<'a> {
+    pub file: &'a dyn WasiFile,
+    result: &'a AtomicBool,
+}
+
+/// A set of [`Subscription`]s passed to [`WasiSched::poll_oneoff`]. Built up
+/// by a snapshot's `poll_oneoff` hostcall from the guest's subscriptions,
+/// and consulted afterwards to see which ones fired.
+#[derive(Default)]
+pub struct Poll<'a> {
+    subs: Vec<Subscription<'a>>,
+}
+
+impl<'a> Poll<'a> {
+    pub fn new() -> Self {
+        Poll { subs: Vec::new() }
+    }
+
+    pub fn subscribe_read(&mut self, file: &'a dyn WasiFile, result: &'a AtomicBool) {
+        self.subs
+            .push(Subscription::Read(FileReadiness { file, result }));
+    }
+
+    pub fn subscribe_write(&mut self, file: &'a dyn WasiFile, result: &'a AtomicBool) {
+        self.subs
+            .push(Subscription::Write(FileReadiness { file, result }));
+    }
+
+    pub fn subscribe_monotonic_clock(&mut self, timeout: Duration, result: &'a AtomicBool) {
+        self.subs
+            .push(Subscription::MonotonicClock { timeout, result });
+    }
+
+    pub fn set_ready(sub: &Subscription<'a>) {
+        match sub {
+            Subscription::Read(r) | Subscription::Write(r) => {
+                r.result.store(true, Ordering::SeqCst)
+            }
+            Subscription::MonotonicClock { result, .. } => result.store(true, Ordering::SeqCst),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.subs.is_empty()
+    }
+
+    pub fn rw_subscriptions(&self) -> impl Iterator<Item = &Subscription<'a>> {
+        self.subs.iter()
+    }
+
+    pub fn earliest_clock_deadline(&self) -> Option<Duration> {
+        self.subs
+            .iter()
+            .filter_map(|s| match s {
+                Subscription::MonotonicClock { timeout, .. } => Some(*timeout),
+                _ => None,
+            })
+            .min()
+    }
+}
+
+/// Scheduling operations a `WasiCtx` needs from its embedder: yielding the
+/// current guest, and multiplexing readiness across the files (and, since
+/// sockets are just another `WasiFile`, listeners and streams) registered in
+/// a [`Poll`].
+#[async_trait::async_trait]
+pub trait WasiSched: Send + Sync {
+    async fn yield_now(&self) -> Result<(), Error>;
+    async fn sleep(&self, duration: Duration) -> Result<(), Error>;
+    async fn poll_oneoff<'a>(&self, poll: &mut Poll<'a>) -> Result<(), Error>;
+}
@@ -0,0 +1,10 @@
+//! The two WASI ABI "snapshots" `wasi-common` implements: the legacy
+//! `wasi_unstable` (aka "snapshot 0", still needed by some older modules)
+//! and `wasi_snapshot_preview1` (aka "snapshot 1", what most tooling
+//! targets today). Both are normally generated from their witx definitions
+//! by the `wiggle` proc macros wired up in `crate::define_wasi!`; the
+//! hand-written pieces in each submodule are the parts of the ABI that need
+//! real logic rather than a mechanical `fd`-table lookup.
+
+pub mod preview_0;
+pub mod preview_1;
@@ -0,0 +1,87 @@
+//! `wasi_unstable`, aka "snapshot 0". Kept around because some older guest
+//! toolchains still target it; new code should prefer
+//! `crate::snapshots::preview_1`.
+
+pub mod types {
+    pub use crate::snapshots::preview_1::types::Exitcode;
+    pub use crate::snapshots::preview_1::types::Fd;
+}
+
+pub mod wasi_unstable {
+    use super::types;
+    use crate::ctx::WasiCtx;
+    use crate::snapshots::preview_1::wasi_snapshot_preview1::exit_error;
+    use crate::SystemTimeSpec;
+    use std::io::{IoSlice, IoSliceMut};
+
+    #[async_trait::async_trait]
+    pub trait WasiUnstable {
+        /// See `wasi_snapshot_preview1::proc_exit` - snapshot 0 defines the
+        /// same noreturn semantics, so it shares the same exit-behavior
+        /// handling.
+        async fn proc_exit(&mut self, status: types::Exitcode) -> anyhow::Error;
+
+        /// See `wasi_snapshot_preview1::fd_read`.
+        async fn fd_read(&mut self, fd: types::Fd, buf: &mut [u8]) -> Result<u32, crate::Error>;
+
+        /// See `wasi_snapshot_preview1::fd_write`.
+        async fn fd_write(&mut self, fd: types::Fd, buf: &[u8]) -> Result<u32, crate::Error>;
+
+        /// See `wasi_snapshot_preview1::fd_allocate`.
+        async fn fd_allocate(
+            &mut self,
+            fd: types::Fd,
+            offset: u64,
+            len: u64,
+        ) -> Result<(), crate::Error>;
+
+        /// See `wasi_snapshot_preview1::fd_filestat_set_times`.
+        async fn fd_filestat_set_times(
+            &mut self,
+            fd: types::Fd,
+            atime: Option<SystemTimeSpec>,
+            mtime: Option<SystemTimeSpec>,
+        ) -> Result<(), crate::Error>;
+    }
+
+    #[async_trait::async_trait]
+    impl WasiUnstable for WasiCtx {
+        async fn proc_exit(&mut self, status: types::Exitcode) -> anyhow::Error {
+            exit_error(status as i32, self.exit_behavior())
+        }
+
+        async fn fd_read(&mut self, fd: types::Fd, buf: &mut [u8]) -> Result<u32, crate::Error> {
+            let file = self.get_file(fd)?;
+            let mut iovs = [IoSliceMut::new(buf)];
+            let n = file.read_vectored(&mut iovs).await?;
+            Ok(n as u32)
+        }
+
+        async fn fd_write(&mut self, fd: types::Fd, buf: &[u8]) -> Result<u32, crate::Error> {
+            let file = self.get_file(fd)?;
+            let iovs = [IoSlice::new(buf)];
+            let n = file.write_vectored(&iovs).await?;
+            Ok(n as u32)
+        }
+
+        async fn fd_allocate(
+            &mut self,
+            fd: types::Fd,
+            offset: u64,
+            len: u64,
+        ) -> Result<(), crate::Error> {
+            let file = self.get_file(fd)?;
+            file.allocate(offset, len).await
+        }
+
+        async fn fd_filestat_set_times(
+            &mut self,
+            fd: types::Fd,
+            atime: Option<SystemTimeSpec>,
+            mtime: Option<SystemTimeSpec>,
+        ) -> Result<(), crate::Error> {
+            let file = self.get_file(fd)?;
+            file.set_times(atime, mtime).await
+        }
+    }
+}
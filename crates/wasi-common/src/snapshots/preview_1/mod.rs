@@ -0,0 +1,186 @@
+//! `wasi_snapshot_preview1`. The `wasi_snapshot_preview1` submodule here is
+//! the `target` of the `wiggle::wasmtime_integration!` invocation in
+//! `crate::define_wasi!`; wiggle generates the `fd`-indexed wasm-facing
+//! hostcalls and dispatches each one to the method of the same name below.
+
+pub mod types {
+    /// The exit code a guest passed to `proc_exit`. WASI defines this as an
+    /// unsigned 32-bit value, but in practice every embedder and every
+    /// guest toolchain treats it as the low byte of a process exit status.
+    pub type Exitcode = u32;
+
+    /// A WASI file descriptor, as used to index `WasiCtx`'s `Table`.
+    pub type Fd = u32;
+
+    /// What a `poll_oneoff` subscription is waiting on. Clock subscriptions
+    /// are not modeled yet - only the `fd`-readiness half needed to
+    /// multiplex sockets and files.
+    #[derive(Debug, Copy, Clone, PartialEq, Eq)]
+    pub enum Interest {
+        Read,
+        Write,
+    }
+}
+
+pub mod wasi_snapshot_preview1 {
+    use super::types;
+    use crate::ctx::{ExitBehavior, WasiCtx};
+    use crate::file::{FdFlags, WasiFile};
+    use crate::sched::Poll;
+    use crate::{Error, ErrorExt, I32Exit, SystemTimeSpec};
+    use std::io::{IoSlice, IoSliceMut};
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use std::sync::Arc;
+
+    #[async_trait::async_trait]
+    pub trait WasiSnapshotPreview1 {
+        /// End the guest with the given exit code. An exit code of 0
+        /// indicates successful termination of the program, and non-zero
+        /// codes indicate some kind of failure.
+        ///
+        /// Under [`ExitBehavior::TrapAndUnwind`] (the default) this returns
+        /// an error carrying an [`I32Exit`], so that wiggle's generated
+        /// trampoline traps and unwinds the wasm stack back to the host
+        /// `call`, where the caller can recover the code - see
+        /// [`crate::i32_exit_status`]. Under [`ExitBehavior::Terminate`] it
+        /// calls `std::process::exit` directly. See [`WasiCtx::exit_behavior`].
+        async fn proc_exit(&mut self, status: types::Exitcode) -> anyhow::Error;
+
+        /// Read from a file descriptor. Dispatches to whatever `WasiFile`
+        /// impl is registered at `fd` - a regular file, a pipe, or (since
+        /// `WasiTcpSocket` is just another `WasiFile`) an accepted socket.
+        async fn fd_read(&mut self, fd: types::Fd, buf: &mut [u8]) -> Result<u32, Error>;
+
+        /// Write to a file descriptor. See `fd_read`.
+        async fn fd_write(&mut self, fd: types::Fd, buf: &[u8]) -> Result<u32, Error>;
+
+        /// Accept a connection on a listening socket fd, returning a new fd
+        /// for the accepted stream.
+        async fn sock_accept(
+            &mut self,
+            fd: types::Fd,
+            fdflags: FdFlags,
+        ) -> Result<types::Fd, Error>;
+
+        /// Block until at least one of `subs` is ready, returning the fds
+        /// that are. Lets a guest multiplex listeners and regular files in
+        /// one call by handing every fd's underlying `WasiFile` to the
+        /// embedder's `WasiSched::poll_oneoff`.
+        async fn poll_oneoff(
+            &mut self,
+            subs: &[(types::Fd, types::Interest)],
+        ) -> Result<Vec<types::Fd>, Error>;
+
+        /// Preallocate storage for a file so that subsequent writes within
+        /// `[offset, offset + len)` don't fail with an out-of-space error.
+        /// See `WasiFile::allocate`.
+        async fn fd_allocate(&mut self, fd: types::Fd, offset: u64, len: u64) -> Result<(), Error>;
+
+        /// Set a file's access and/or modification time; `None` leaves that
+        /// field unchanged. See `WasiFile::set_times`.
+        async fn fd_filestat_set_times(
+            &mut self,
+            fd: types::Fd,
+            atime: Option<SystemTimeSpec>,
+            mtime: Option<SystemTimeSpec>,
+        ) -> Result<(), Error>;
+    }
+
+    #[async_trait::async_trait]
+    impl WasiSnapshotPreview1 for WasiCtx {
+        async fn proc_exit(&mut self, status: types::Exitcode) -> anyhow::Error {
+            exit_error(status as i32, self.exit_behavior())
+        }
+
+        async fn fd_read(&mut self, fd: types::Fd, buf: &mut [u8]) -> Result<u32, Error> {
+            let file = self.get_file(fd)?;
+            let mut iovs = [IoSliceMut::new(buf)];
+            let n = file.read_vectored(&mut iovs).await?;
+            Ok(n as u32)
+        }
+
+        async fn fd_write(&mut self, fd: types::Fd, buf: &[u8]) -> Result<u32, Error> {
+            let file = self.get_file(fd)?;
+            let iovs = [IoSlice::new(buf)];
+            let n = file.write_vectored(&iovs).await?;
+            Ok(n as u32)
+        }
+
+        async fn sock_accept(
+            &mut self,
+            fd: types::Fd,
+            fdflags: FdFlags,
+        ) -> Result<types::Fd, Error> {
+            let listener = self.get_file(fd)?;
+            let accepted = listener.sock_accept(fdflags).await?;
+            self.table()
+                .push(Arc::new(accepted) as _)
+                .map_err(|_| Error::too_big())
+        }
+
+        async fn poll_oneoff(
+            &mut self,
+            subs: &[(types::Fd, types::Interest)],
+        ) -> Result<Vec<types::Fd>, Error> {
+            let entries = subs
+                .iter()
+                .map(|(fd, interest)| Ok((*fd, *interest, self.get_file(*fd)?)))
+                .collect::<Result<Vec<_>, Error>>()?;
+            let ready: Vec<AtomicBool> = entries
+                .iter()
+                .map(|(_, _, file)| AtomicBool::new(file.always_ready()))
+                .collect();
+
+            let mut poll = Poll::new();
+            for ((_, interest, file), result) in entries.iter().zip(ready.iter()) {
+                if file.always_ready() {
+                    continue;
+                }
+                let file: &dyn WasiFile = &***file;
+                match interest {
+                    types::Interest::Read => poll.subscribe_read(file, result),
+                    types::Interest::Write => poll.subscribe_write(file, result),
+                }
+            }
+
+            // An always-ready entry already satisfies the call, so only
+            // wait on the scheduler when nothing is ready yet.
+            if !ready.iter().any(|r| r.load(Ordering::SeqCst)) {
+                self.sched.poll_oneoff(&mut poll).await?;
+            }
+
+            Ok(entries
+                .iter()
+                .zip(ready.iter())
+                .filter(|(_, r)| r.load(Ordering::SeqCst))
+                .map(|((fd, _, _), _)| *fd)
+                .collect())
+        }
+
+        async fn fd_allocate(&mut self, fd: types::Fd, offset: u64, len: u64) -> Result<(), Error> {
+            let file = self.get_file(fd)?;
+            file.allocate(offset, len).await
+        }
+
+        async fn fd_filestat_set_times(
+            &mut self,
+            fd: types::Fd,
+            atime: Option<SystemTimeSpec>,
+            mtime: Option<SystemTimeSpec>,
+        ) -> Result<(), Error> {
+            let file = self.get_file(fd)?;
+            file.set_times(atime, mtime).await
+        }
+    }
+
+    /// Package a guest's requested exit code the way `behavior` asks for.
+    /// Every `proc_exit`/`wasi_unstable::proc_exit` shim funnels through
+    /// here so the two snapshots can't drift on how they honor the
+    /// setting.
+    pub(crate) fn exit_error(code: i32, behavior: ExitBehavior) -> anyhow::Error {
+        match behavior {
+            ExitBehavior::TrapAndUnwind => Error::from(I32Exit(code)).into_anyhow(),
+            ExitBehavior::Terminate => std::process::exit(code),
+        }
+    }
+}
@@ -0,0 +1,163 @@
+//! Socket resources: a `WasiListener` for a preopened, bound/listening TCP
+//! listener, and a `WasiTcpSocket` for the streams it accepts. Both are
+//! `WasiFile` impls (parallel to `file::WasiFile`'s relationship to regular
+//! files) so the snapshot 0/1 `fd`-indexed hostcalls can treat a socket fd
+//! exactly like any other file descriptor, and so a guest can multiplex
+//! listeners and regular files in one `poll_oneoff` call.
+
+use crate::file::{FdFlags, FileType, WasiFile};
+use crate::{Error, ErrorExt};
+use std::any::Any;
+use std::io::{self, IoSlice, IoSliceMut, Read, Write};
+use std::net::{TcpListener, TcpStream};
+#[cfg(unix)]
+use std::os::fd::{AsRawFd, RawFd};
+use std::sync::Mutex;
+
+/// A TCP listener handed to the guest as a preopened fd via
+/// `WasiCtxBuilder::preopen_socket`. The only thing a guest can do with it
+/// is `sock_accept` - reads and writes happen on the streams it accepts,
+/// represented as separate [`WasiTcpSocket`] fds.
+pub struct WasiListener {
+    listener: TcpListener,
+}
+
+impl WasiListener {
+    /// Wrap an already bound/listening `TcpListener`, putting it into
+    /// nonblocking mode so `sock_accept` and `poll_oneoff` never suspend
+    /// the whole process on one guest's socket.
+    pub fn from_std(listener: TcpListener) -> io::Result<Self> {
+        listener.set_nonblocking(true)?;
+        Ok(WasiListener { listener })
+    }
+}
+
+#[cfg(unix)]
+impl AsRawFd for WasiListener {
+    fn as_raw_fd(&self) -> RawFd {
+        self.listener.as_raw_fd()
+    }
+}
+
+#[async_trait::async_trait]
+impl WasiFile for WasiListener {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    async fn get_filetype(&self) -> Result<FileType, Error> {
+        Ok(FileType::SocketStream)
+    }
+
+    /// Nonblocking: if there's no pending connection this returns
+    /// [`ErrorExt::would_block`] rather than suspending, so a guest driving
+    /// a poll-then-accept loop (`poll_oneoff` for readability, then
+    /// `sock_accept`) can treat it as "try again" instead of a fatal trap.
+    async fn sock_accept(&self, _fdflags: FdFlags) -> Result<Box<dyn WasiFile>, Error> {
+        let (stream, _addr) = self.listener.accept().map_err(|e| {
+            if e.kind() == io::ErrorKind::WouldBlock {
+                Error::would_block()
+            } else {
+                Error::from(e)
+            }
+        })?;
+        Ok(Box::new(WasiTcpSocket::from_std(stream)?))
+    }
+
+    async fn read_vectored<'a>(&self, _bufs: &mut [IoSliceMut<'a>]) -> Result<u64, Error> {
+        Err(Error::badf())
+    }
+    async fn read_vectored_at<'a>(
+        &self,
+        _bufs: &mut [IoSliceMut<'a>],
+        _offset: u64,
+    ) -> Result<u64, Error> {
+        Err(Error::badf())
+    }
+    async fn write_vectored<'a>(&self, _bufs: &[IoSlice<'a>]) -> Result<u64, Error> {
+        Err(Error::badf())
+    }
+    async fn write_vectored_at<'a>(
+        &self,
+        _bufs: &[IoSlice<'a>],
+        _offset: u64,
+    ) -> Result<u64, Error> {
+        Err(Error::badf())
+    }
+    async fn seek(&self, _pos: io::SeekFrom) -> Result<u64, Error> {
+        Err(Error::badf())
+    }
+    async fn peek(&self, _buf: &mut [u8]) -> Result<u64, Error> {
+        Ok(0)
+    }
+}
+
+/// A TCP stream accepted from a [`WasiListener`] (or, once a guest holds
+/// one, just another readable/writable fd).
+pub struct WasiTcpSocket {
+    stream: Mutex<TcpStream>,
+}
+
+impl WasiTcpSocket {
+    pub fn from_std(stream: TcpStream) -> io::Result<Self> {
+        stream.set_nonblocking(true)?;
+        Ok(WasiTcpSocket {
+            stream: Mutex::new(stream),
+        })
+    }
+
+    /// The address of the peer this stream is connected to, if it can
+    /// still be determined. Used by [`crate::permissions::PermissionedFile`]
+    /// to check an accepted connection against network permissions.
+    pub fn peer_addr(&self) -> Option<std::net::SocketAddr> {
+        self.stream.lock().unwrap().peer_addr().ok()
+    }
+}
+
+#[cfg(unix)]
+impl AsRawFd for WasiTcpSocket {
+    fn as_raw_fd(&self) -> RawFd {
+        self.stream.lock().unwrap().as_raw_fd()
+    }
+}
+
+#[async_trait::async_trait]
+impl WasiFile for WasiTcpSocket {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    async fn get_filetype(&self) -> Result<FileType, Error> {
+        Ok(FileType::SocketStream)
+    }
+
+    async fn read_vectored<'a>(&self, bufs: &mut [IoSliceMut<'a>]) -> Result<u64, Error> {
+        let n = self.stream.lock().unwrap().read_vectored(bufs)?;
+        Ok(n as u64)
+    }
+    async fn read_vectored_at<'a>(
+        &self,
+        _bufs: &mut [IoSliceMut<'a>],
+        _offset: u64,
+    ) -> Result<u64, Error> {
+        Err(Error::seek_pipe())
+    }
+    async fn write_vectored<'a>(&self, bufs: &[IoSlice<'a>]) -> Result<u64, Error> {
+        let n = self.stream.lock().unwrap().write_vectored(bufs)?;
+        Ok(n as u64)
+    }
+    async fn write_vectored_at<'a>(
+        &self,
+        _bufs: &[IoSlice<'a>],
+        _offset: u64,
+    ) -> Result<u64, Error> {
+        Err(Error::seek_pipe())
+    }
+    async fn seek(&self, _pos: io::SeekFrom) -> Result<u64, Error> {
+        Err(Error::seek_pipe())
+    }
+    async fn peek(&self, buf: &mut [u8]) -> Result<u64, Error> {
+        let n = self.stream.lock().unwrap().peek(buf)?;
+        Ok(n as u64)
+    }
+}
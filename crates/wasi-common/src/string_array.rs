@@ -0,0 +1,76 @@
+use std::fmt;
+
+/// An array of NUL-terminated strings, as used by `args_get`/`environ_get`
+/// and friends. Validates eagerly so the snapshot implementations can hand
+/// back pointers without re-checking lengths on every call.
+#[derive(Clone, Default, Debug, PartialEq, Eq)]
+pub struct StringArray {
+    strings: Vec<String>,
+    number_elements: usize,
+    cumulative_size: usize,
+}
+
+impl StringArray {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn from<S: AsRef<str>>(input: impl IntoIterator<Item = S>) -> Result<Self, StringArrayError> {
+        let mut strings = Vec::new();
+        for s in input {
+            let s = s.as_ref();
+            if s.bytes().any(|b| b == 0) {
+                return Err(StringArrayError::ContainsNul);
+            }
+            strings.push(s.to_owned());
+        }
+        let number_elements = strings.len();
+        if number_elements >= u32::MAX as usize {
+            return Err(StringArrayError::NumberElements(number_elements));
+        }
+        let cumulative_size = strings.iter().map(|s| s.len() + 1).sum();
+        if cumulative_size >= u32::MAX as usize {
+            return Err(StringArrayError::CumulativeSize(cumulative_size));
+        }
+        Ok(StringArray {
+            strings,
+            number_elements,
+            cumulative_size,
+        })
+    }
+
+    pub fn number_elements(&self) -> usize {
+        self.number_elements
+    }
+
+    pub fn cumulative_size(&self) -> usize {
+        self.cumulative_size
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &String> {
+        self.strings.iter()
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum StringArrayError {
+    ContainsNul,
+    NumberElements(usize),
+    CumulativeSize(usize),
+}
+
+impl fmt::Display for StringArrayError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            StringArrayError::ContainsNul => write!(f, "string contains embedded NUL byte"),
+            StringArrayError::NumberElements(n) => {
+                write!(f, "number of elements {n} exceeds u32::MAX")
+            }
+            StringArrayError::CumulativeSize(n) => {
+                write!(f, "cumulative size {n} exceeds u32::MAX")
+            }
+        }
+    }
+}
+
+impl std::error::Error for StringArrayError {}
@@ -0,0 +1,165 @@
+//! Synchronous, cap-std-backed implementation of [`WasiFile`], for
+//! embedders using `Config::async_support(false)`. The methods below do
+//! blocking syscalls directly rather than actually suspending - that's
+//! sound here because a sync-mode wasmtime integration drives these
+//! futures with a "dummy executor" that always polls them to `Ready`
+//! immediately. [`crate::tokio`] reuses the same blocking calls, run via
+//! `tokio::task::block_in_place`, for embedders that can't make that
+//! assumption.
+//!
+//! `wasi-common` still provides no `WasiDir` backed by a real directory
+//! (only the `permissions::PermissionedDir` wrapper): an embedder hands a guest
+//! one of these by opening a `cap_std::fs::File` itself and wrapping it
+//! with [`File::from_cap_std`], e.g. via `WasiCtx::insert_file`.
+
+use crate::file::{FileType, WasiFile};
+use crate::{Error, SystemTimeSpec};
+use fs_set_times::SetTimes;
+use std::any::Any;
+use std::io::{IoSlice, IoSliceMut, SeekFrom};
+use system_interface::fs::FileIoExt;
+use system_interface::io::IoExt;
+
+fn to_fs_set_times(spec: Option<SystemTimeSpec>) -> Option<fs_set_times::SystemTimeSpec> {
+    spec.map(|s| match s {
+        SystemTimeSpec::Absolute(t) => fs_set_times::SystemTimeSpec::Absolute(t.into_std()),
+        SystemTimeSpec::SymbolicNow => fs_set_times::SystemTimeSpec::SymbolicNow,
+    })
+}
+
+pub(crate) fn get_filetype(file: &cap_std::fs::File) -> Result<FileType, Error> {
+    let meta = file.metadata()?;
+    Ok(if meta.is_dir() {
+        FileType::Directory
+    } else if meta.file_type().is_symlink() {
+        FileType::SymbolicLink
+    } else {
+        FileType::RegularFile
+    })
+}
+
+/// Allocate space in the file, increasing its size as needed, and
+/// ensuring there are no holes under `offset..offset+len`.
+pub(crate) fn allocate(file: &cap_std::fs::File, offset: u64, len: u64) -> Result<(), Error> {
+    file.allocate(offset, len)?;
+    Ok(())
+}
+
+/// Set the file's access and modification times, supporting "now", "this
+/// absolute value", and (via `None`) "leave unchanged" for each
+/// independently, as encoded by the WASI `fstflags`.
+pub(crate) fn set_times(
+    file: &cap_std::fs::File,
+    atime: Option<SystemTimeSpec>,
+    mtime: Option<SystemTimeSpec>,
+) -> Result<(), Error> {
+    file.set_times(to_fs_set_times(atime), to_fs_set_times(mtime))?;
+    Ok(())
+}
+
+pub(crate) fn read_vectored(
+    file: &cap_std::fs::File,
+    bufs: &mut [IoSliceMut<'_>],
+) -> Result<u64, Error> {
+    Ok(file.read_vectored(bufs)? as u64)
+}
+
+pub(crate) fn read_vectored_at(
+    file: &cap_std::fs::File,
+    bufs: &mut [IoSliceMut<'_>],
+    offset: u64,
+) -> Result<u64, Error> {
+    Ok(file.read_vectored_at(bufs, offset)? as u64)
+}
+
+pub(crate) fn write_vectored(file: &cap_std::fs::File, bufs: &[IoSlice<'_>]) -> Result<u64, Error> {
+    Ok(file.write_vectored(bufs)? as u64)
+}
+
+pub(crate) fn write_vectored_at(
+    file: &cap_std::fs::File,
+    bufs: &[IoSlice<'_>],
+    offset: u64,
+) -> Result<u64, Error> {
+    Ok(file.write_vectored_at(bufs, offset)? as u64)
+}
+
+pub(crate) fn seek(file: &cap_std::fs::File, pos: SeekFrom) -> Result<u64, Error> {
+    Ok(FileIoExt::seek(file, pos)?)
+}
+
+pub(crate) fn peek(file: &cap_std::fs::File, buf: &mut [u8]) -> Result<u64, Error> {
+    Ok(file.peek(buf)? as u64)
+}
+
+/// A [`WasiFile`] backed by a real `cap_std::fs::File`.
+pub struct File {
+    file: cap_std::fs::File,
+}
+
+impl File {
+    pub fn from_cap_std(file: cap_std::fs::File) -> Self {
+        Self { file }
+    }
+}
+
+#[async_trait::async_trait]
+impl WasiFile for File {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    async fn get_filetype(&self) -> Result<FileType, Error> {
+        get_filetype(&self.file)
+    }
+
+    async fn datasync(&self) -> Result<(), Error> {
+        self.file.sync_data()?;
+        Ok(())
+    }
+
+    async fn sync(&self) -> Result<(), Error> {
+        self.file.sync_all()?;
+        Ok(())
+    }
+
+    async fn allocate(&self, offset: u64, len: u64) -> Result<(), Error> {
+        allocate(&self.file, offset, len)
+    }
+
+    async fn set_times(
+        &self,
+        atime: Option<SystemTimeSpec>,
+        mtime: Option<SystemTimeSpec>,
+    ) -> Result<(), Error> {
+        set_times(&self.file, atime, mtime)
+    }
+
+    async fn read_vectored<'a>(&self, bufs: &mut [IoSliceMut<'a>]) -> Result<u64, Error> {
+        read_vectored(&self.file, bufs)
+    }
+
+    async fn read_vectored_at<'a>(
+        &self,
+        bufs: &mut [IoSliceMut<'a>],
+        offset: u64,
+    ) -> Result<u64, Error> {
+        read_vectored_at(&self.file, bufs, offset)
+    }
+
+    async fn write_vectored<'a>(&self, bufs: &[IoSlice<'a>]) -> Result<u64, Error> {
+        write_vectored(&self.file, bufs)
+    }
+
+    async fn write_vectored_at<'a>(&self, bufs: &[IoSlice<'a>], offset: u64) -> Result<u64, Error> {
+        write_vectored_at(&self.file, bufs, offset)
+    }
+
+    async fn seek(&self, pos: SeekFrom) -> Result<u64, Error> {
+        seek(&self.file, pos)
+    }
+
+    async fn peek(&self, buf: &mut [u8]) -> Result<u64, Error> {
+        peek(&self.file, buf)
+    }
+}
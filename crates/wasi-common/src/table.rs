@@ -0,0 +1,106 @@
+use std::any::Any;
+use std::collections::HashMap;
+use std::sync::Arc;
+
+/// The `Table` type is designed to map u32 handles to resources. The table is
+/// now part of the public interface to `wasi-common`, as it is general
+/// purpose enough to provide for other WASI implementations besides WASI
+/// Preview 1.
+///
+/// Resources stored in the table are responsible for their own interior
+/// mutability, if any is required - the table itself just hands out cloned
+/// `Arc`s.
+#[derive(Debug)]
+pub struct Table {
+    map: HashMap<u32, Arc<dyn Any + Send + Sync>>,
+    next_key: u32,
+}
+
+impl Table {
+    /// Create an empty table. New insertions will begin at 3, above stdio.
+    pub fn new() -> Self {
+        Table {
+            map: HashMap::new(),
+            next_key: 3, // 0, 1 and 2 are reserved for stdio
+        }
+    }
+
+    /// Insert a resource at the next available index.
+    pub fn push(&mut self, a: Arc<dyn Any + Send + Sync>) -> Result<u32, TableError> {
+        // NOTE: The performance of this new key calculation could be very bad once keys wrap
+        // around.
+        if self.map.len() == u32::MAX as usize {
+            return Err(TableError::Full);
+        }
+        loop {
+            let key = self.next_key;
+            self.next_key = self.next_key.wrapping_add(1);
+            if let std::collections::hash_map::Entry::Vacant(e) = self.map.entry(key) {
+                e.insert(a);
+                return Ok(key);
+            }
+        }
+    }
+
+    /// Insert a resource at a certain index.
+    pub fn insert_at(&mut self, key: u32, a: Arc<dyn Any + Send + Sync>) {
+        self.map.insert(key, a);
+    }
+
+    /// Check if the table has a resource at the given index.
+    pub fn contains_key(&self, key: u32) -> bool {
+        self.map.contains_key(&key)
+    }
+
+    /// Check if the resource at a given index can be downcast to a given
+    /// type. Note: Preview 1 ABI needs to return different error codes
+    /// depending on if a different resource is expected than is found at a
+    /// given index.
+    pub fn is<T: Any + Sized>(&self, key: u32) -> bool {
+        self.map.get(&key).is_some_and(|r| r.is::<T>())
+    }
+
+    /// Get a resource from the table, downcast to a concrete type.
+    pub fn get<T: Any + Send + Sync + Sized>(&self, key: u32) -> Option<Arc<T>> {
+        Arc::clone(self.map.get(&key)?).downcast::<T>().ok()
+    }
+
+    /// Remove a resource at a given index.
+    pub fn delete(&mut self, key: u32) -> Option<Arc<dyn Any + Send + Sync>> {
+        self.map.remove(&key)
+    }
+
+    /// Renumber a resource from one index to another.
+    pub fn renumber(&mut self, from: u32, to: u32) -> Result<(), TableError> {
+        let item = self.map.get(&from).ok_or(TableError::NotPresent)?.clone();
+        self.map.insert(to, item);
+        self.map.remove(&from);
+        Ok(())
+    }
+}
+
+impl Default for Table {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Failure modes for the handful of `Table` operations that can fail.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum TableError {
+    /// The table has exhausted the full `u32` key space.
+    Full,
+    /// No resource is present at the requested key.
+    NotPresent,
+}
+
+impl std::fmt::Display for TableError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TableError::Full => write!(f, "table has no more available keys"),
+            TableError::NotPresent => write!(f, "no resource at this table key"),
+        }
+    }
+}
+
+impl std::error::Error for TableError {}
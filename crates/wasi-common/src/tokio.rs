@@ -0,0 +1,81 @@
+//! Tokio-backed implementation of [`WasiFile`]. Reuses
+//! [`crate::sync`]'s blocking syscalls, but runs each one via
+//! `tokio::task::block_in_place` so a slow filesystem op doesn't stall
+//! the runtime's other tasks - unlike `crate::sync::File`, which assumes
+//! its caller polls it with a dummy executor that never actually yields.
+
+use crate::file::{FileType, WasiFile};
+use crate::sync;
+use crate::{Error, SystemTimeSpec};
+use std::any::Any;
+use std::io::{IoSlice, IoSliceMut, SeekFrom};
+
+/// A [`WasiFile`] backed by a real `cap_std::fs::File`.
+pub struct File {
+    file: cap_std::fs::File,
+}
+
+impl File {
+    pub fn from_cap_std(file: cap_std::fs::File) -> Self {
+        Self { file }
+    }
+}
+
+#[async_trait::async_trait]
+impl WasiFile for File {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    async fn get_filetype(&self) -> Result<FileType, Error> {
+        tokio::task::block_in_place(|| sync::get_filetype(&self.file))
+    }
+
+    async fn datasync(&self) -> Result<(), Error> {
+        tokio::task::block_in_place(|| Ok(self.file.sync_data()?))
+    }
+
+    async fn sync(&self) -> Result<(), Error> {
+        tokio::task::block_in_place(|| Ok(self.file.sync_all()?))
+    }
+
+    async fn allocate(&self, offset: u64, len: u64) -> Result<(), Error> {
+        tokio::task::block_in_place(|| sync::allocate(&self.file, offset, len))
+    }
+
+    async fn set_times(
+        &self,
+        atime: Option<SystemTimeSpec>,
+        mtime: Option<SystemTimeSpec>,
+    ) -> Result<(), Error> {
+        tokio::task::block_in_place(|| sync::set_times(&self.file, atime, mtime))
+    }
+
+    async fn read_vectored<'a>(&self, bufs: &mut [IoSliceMut<'a>]) -> Result<u64, Error> {
+        tokio::task::block_in_place(|| sync::read_vectored(&self.file, bufs))
+    }
+
+    async fn read_vectored_at<'a>(
+        &self,
+        bufs: &mut [IoSliceMut<'a>],
+        offset: u64,
+    ) -> Result<u64, Error> {
+        tokio::task::block_in_place(|| sync::read_vectored_at(&self.file, bufs, offset))
+    }
+
+    async fn write_vectored<'a>(&self, bufs: &[IoSlice<'a>]) -> Result<u64, Error> {
+        tokio::task::block_in_place(|| sync::write_vectored(&self.file, bufs))
+    }
+
+    async fn write_vectored_at<'a>(&self, bufs: &[IoSlice<'a>], offset: u64) -> Result<u64, Error> {
+        tokio::task::block_in_place(|| sync::write_vectored_at(&self.file, bufs, offset))
+    }
+
+    async fn seek(&self, pos: SeekFrom) -> Result<u64, Error> {
+        tokio::task::block_in_place(|| sync::seek(&self.file, pos))
+    }
+
+    async fn peek(&self, buf: &mut [u8]) -> Result<u64, Error> {
+        tokio::task::block_in_place(|| sync::peek(&self.file, buf))
+    }
+}